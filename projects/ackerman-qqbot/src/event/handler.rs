@@ -0,0 +1,127 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use regex::Regex;
+use tokio::sync::broadcast;
+
+use super::{QQBotEvent, QQBotEventKind};
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// 事件处理器，返回值表示这次调用是否"命中"（用于 `block` 语义），而不是是否发生了错误
+pub trait EventHandler: Send + Sync {
+    fn handle(&self, event: QQBotEvent) -> BoxFuture<'static, bool>;
+}
+
+impl<F, Fut> EventHandler for F
+where
+    F: Fn(QQBotEvent) -> Fut + Send + Sync,
+    Fut: Future<Output = bool> + Send + 'static,
+{
+    fn handle(&self, event: QQBotEvent) -> BoxFuture<'static, bool> {
+        Box::pin(self(event))
+    }
+}
+
+enum Matcher {
+    FullMatch(String),
+    Prefix(String),
+    Regex(Regex),
+    Event(QQBotEventKind),
+}
+
+impl Matcher {
+    fn matches(&self, event: &QQBotEvent) -> bool {
+        match self {
+            Matcher::FullMatch(text) => event.text().map_or(false, |content| &content == text),
+            Matcher::Prefix(prefix) => event.text().map_or(false, |content| content.starts_with(prefix.as_str())),
+            Matcher::Regex(regex) => event.text().map_or(false, |content| regex.is_match(&content)),
+            Matcher::Event(kind) => event.kind() == *kind,
+        }
+    }
+}
+
+struct Registration {
+    matcher: Matcher,
+    block: bool,
+    handler: Arc<dyn EventHandler>,
+}
+
+/// 插件式的事件总线：按注册顺序匹配文本/事件处理器，同时把每个解码后的事件广播给所有订阅者。
+///
+/// `block` 为 `true`（`on_full_match`/`on_prefix`/`on_regex` 会设置）时，一旦某个处理器命中，就不会再尝试优先级更低的处理器；
+/// `on_event` 注册的处理器 `block` 为 `false`，命中后不会拦截后面的处理器。
+pub struct EventBus {
+    sender: broadcast::Sender<QQBotEvent>,
+    registrations: Mutex<Vec<Registration>>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        let (sender, _receiver) = broadcast::channel(1024);
+        Self { sender, registrations: Mutex::new(Vec::new()) }
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn on_full_match<H>(&self, text: impl Into<String>, handler: H)
+    where
+        H: EventHandler + 'static,
+    {
+        self.register(Matcher::FullMatch(text.into()), true, handler)
+    }
+    pub fn on_prefix<H>(&self, prefix: impl Into<String>, handler: H)
+    where
+        H: EventHandler + 'static,
+    {
+        self.register(Matcher::Prefix(prefix.into()), true, handler)
+    }
+    pub fn on_regex<H>(&self, pattern: &str, handler: H) -> Result<(), regex::Error>
+    where
+        H: EventHandler + 'static,
+    {
+        let regex = Regex::new(pattern)?;
+        self.register(Matcher::Regex(regex), true, handler);
+        Ok(())
+    }
+    pub fn on_event<H>(&self, kind: QQBotEventKind, handler: H)
+    where
+        H: EventHandler + 'static,
+    {
+        self.register(Matcher::Event(kind), false, handler)
+    }
+    fn register<H>(&self, matcher: Matcher, block: bool, handler: H)
+    where
+        H: EventHandler + 'static,
+    {
+        self.registrations.lock().unwrap().push(Registration { matcher, block, handler: Arc::new(handler) });
+    }
+    /// 订阅底层的 broadcast 总线，拿到原始的解码事件流
+    pub fn subscribe(&self) -> broadcast::Receiver<QQBotEvent> {
+        self.sender.subscribe()
+    }
+    /// 把一个解码后的事件分发给所有订阅者和匹配的处理器
+    pub async fn publish(&self, event: QQBotEvent) {
+        let _ = self.sender.send(event.clone());
+        let matched: Vec<_> = {
+            let registrations = self.registrations.lock().unwrap();
+            registrations
+                .iter()
+                .filter(|registration| registration.matcher.matches(&event))
+                .map(|registration| (registration.handler.clone(), registration.block))
+                .collect()
+        };
+        for (handler, block) in matched {
+            let hit = handler.handle(event.clone()).await;
+            if hit && block {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub use self::handler::{EventBus, EventHandler};
+use crate::{message::Message, wss::User};
+
+mod handler;
+
+/// 网关 `t` 字段对应的事件种类，用于 [`EventBus::on_event`] 按类型订阅
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum QQBotEventKind {
+    AtMessageCreate,
+    MessageCreate,
+    DirectMessageCreate,
+    GuildMemberAdd,
+    GuildMemberUpdate,
+    GuildMemberRemove,
+    /// 暂未单独建模的事件，保留原始 `t` 与载荷
+    Unknown,
+}
+
+/// 解码后的网关事件，`op == 0` 且 `t` 非空时才会产生（READY/RESUMED 这类未单独建模的 `t` 解码为 [`QQBotEvent::Unknown`]）
+#[derive(Clone, Debug)]
+pub enum QQBotEvent {
+    AtMessageCreate(MessageCreateEvent),
+    MessageCreate(MessageCreateEvent),
+    DirectMessageCreate(MessageCreateEvent),
+    GuildMemberAdd(GuildMemberEvent),
+    GuildMemberUpdate(GuildMemberEvent),
+    GuildMemberRemove(GuildMemberEvent),
+    Unknown { t: String, raw: Value },
+}
+
+impl QQBotEvent {
+    /// 依据网关下发的 `t` 字段把原始 `d` 载荷解码成对应的事件，解码失败时退化为 [`QQBotEvent::Unknown`]
+    pub fn decode(t: &str, raw: Value) -> Self {
+        match t {
+            "AT_MESSAGE_CREATE" => Self::from_value(raw, Self::AtMessageCreate, t),
+            "MESSAGE_CREATE" => Self::from_value(raw, Self::MessageCreate, t),
+            "DIRECT_MESSAGE_CREATE" => Self::from_value(raw, Self::DirectMessageCreate, t),
+            "GUILD_MEMBER_ADD" => Self::from_value(raw, Self::GuildMemberAdd, t),
+            "GUILD_MEMBER_UPDATE" => Self::from_value(raw, Self::GuildMemberUpdate, t),
+            "GUILD_MEMBER_REMOVE" => Self::from_value(raw, Self::GuildMemberRemove, t),
+            _ => Self::Unknown { t: t.to_string(), raw },
+        }
+    }
+    fn from_value<T, F>(raw: Value, wrap: F, t: &str) -> Self
+    where
+        T: for<'de> Deserialize<'de>,
+        F: FnOnce(T) -> Self,
+    {
+        match serde_json::from_value(raw.clone()) {
+            Ok(payload) => wrap(payload),
+            Err(error) => {
+                println!("    事件 {} 解码失败: {}", t, error);
+                Self::Unknown { t: t.to_string(), raw }
+            }
+        }
+    }
+    pub fn kind(&self) -> QQBotEventKind {
+        match self {
+            Self::AtMessageCreate(_) => QQBotEventKind::AtMessageCreate,
+            Self::MessageCreate(_) => QQBotEventKind::MessageCreate,
+            Self::DirectMessageCreate(_) => QQBotEventKind::DirectMessageCreate,
+            Self::GuildMemberAdd(_) => QQBotEventKind::GuildMemberAdd,
+            Self::GuildMemberUpdate(_) => QQBotEventKind::GuildMemberUpdate,
+            Self::GuildMemberRemove(_) => QQBotEventKind::GuildMemberRemove,
+            Self::Unknown { .. } => QQBotEventKind::Unknown,
+        }
+    }
+    /// 消息类事件的正文，用于 `on_full_match`/`on_prefix`/`on_regex` 的文本匹配
+    pub fn text(&self) -> Option<String> {
+        match self {
+            Self::AtMessageCreate(m) | Self::MessageCreate(m) | Self::DirectMessageCreate(m) => {
+                Some(m.content.to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MessageCreateEvent {
+    pub id: String,
+    pub channel_id: String,
+    #[serde(default)]
+    pub guild_id: String,
+    pub author: User,
+    pub content: Message,
+    pub timestamp: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GuildMemberEvent {
+    pub guild_id: String,
+    #[serde(default)]
+    pub nick: String,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub joined_at: String,
+    pub user: User,
+}
@@ -0,0 +1,147 @@
+use std::{
+    fmt,
+    fmt::{Display, Formatter},
+    mem::take,
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// QQ 频道消息 `content` 里内嵌的一个控制 token 或一段纯文本
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MessageSegment {
+    Text(String),
+    /// `<@!user_id>`，@ 某个成员
+    At { user_id: String },
+    /// `<@everyone>`，@ 全体成员
+    AtAll,
+    /// `<#channel_id>`，子频道链接
+    Channel { id: String },
+    /// `<emoji:id>`，内置表情
+    Emoji { id: String },
+}
+
+/// 解析过的消息正文，`Display`/序列化会把各个片段重新拼回 QQ 使用的控制 token 字符串
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Message(pub Vec<MessageSegment>);
+
+impl Message {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self(vec![MessageSegment::Text(text.into())])
+    }
+    pub fn push(&mut self, segment: MessageSegment) -> &mut Self {
+        self.0.push(segment);
+        self
+    }
+    /// 把 `<@!123>`、`<#456>`、`<emoji:4>` 这类控制 token 从原始字符串里切出来，并把 `&amp;`/`&lt;`/`&gt;`
+    /// 这三个被转义过的字符解码回 `&`/`<`/`>`；无法识别或者没有闭合的 `<...>` 一律原样当作文本保留，
+    /// 不会丢字符也不会 panic。
+    pub fn parse(raw: &str) -> Self {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut rest = raw;
+        while let Some(start) = rest.find('<') {
+            literal.push_str(&rest[..start]);
+            let after = &rest[start..];
+            match after.find('>') {
+                Some(end) => {
+                    let token = &after[..=end];
+                    match Self::parse_token(token) {
+                        Some(segment) => {
+                            if !literal.is_empty() {
+                                segments.push(MessageSegment::Text(Self::decode_entities(&take(&mut literal))));
+                            }
+                            segments.push(segment);
+                        }
+                        None => literal.push_str(token),
+                    }
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    literal.push_str(after);
+                    rest = "";
+                    break;
+                }
+            }
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            segments.push(MessageSegment::Text(Self::decode_entities(&literal)));
+        }
+        Self(segments)
+    }
+    /// QQ 用 `&amp;`/`&lt;`/`&gt;` 转义纯文本里的 `&`/`<`/`>`，避免 `<`/`>` 被误认成控制 token 的边界；
+    /// 必须先解 `&lt;`/`&gt;` 再解 `&amp;`，否则 `&amp;lt;`（转义后的字面量 `&lt;`）会被多解一层。
+    fn decode_entities(text: &str) -> String {
+        text.replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&")
+    }
+    /// 编码顺序和 [`Self::decode_entities`] 相反：必须先转义 `&`，否则转义 `<`/`>` 产生的 `&lt;`/`&gt;`
+    /// 会被再转义一遍变成 `&amp;lt;`
+    fn encode_entities(text: &str) -> String {
+        text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+    }
+    fn parse_token(token: &str) -> Option<MessageSegment> {
+        let inner = token.strip_prefix('<')?.strip_suffix('>')?;
+        if inner == "@everyone" {
+            return Some(MessageSegment::AtAll);
+        }
+        if let Some(id) = inner.strip_prefix("@!") {
+            return Self::require_digits(id).map(|id| MessageSegment::At { user_id: id });
+        }
+        if let Some(id) = inner.strip_prefix('#') {
+            return Self::require_digits(id).map(|id| MessageSegment::Channel { id });
+        }
+        if let Some(id) = inner.strip_prefix("emoji:") {
+            return Self::require_digits(id).map(|id| MessageSegment::Emoji { id });
+        }
+        None
+    }
+    fn require_digits(id: &str) -> Option<String> {
+        if !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()) { Some(id.to_string()) } else { None }
+    }
+}
+
+impl Display for MessageSegment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageSegment::Text(text) => f.write_str(&Message::encode_entities(text)),
+            MessageSegment::At { user_id } => write!(f, "<@!{}>", user_id),
+            MessageSegment::AtAll => f.write_str("<@everyone>"),
+            MessageSegment::Channel { id } => write!(f, "<#{}>", id),
+            MessageSegment::Emoji { id } => write!(f, "<emoji:{}>", id),
+        }
+    }
+}
+
+impl Display for Message {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for segment in &self.0 {
+            Display::fmt(segment, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<&str> for Message {
+    fn from(raw: &str) -> Self {
+        Message::parse(raw)
+    }
+}
+
+impl From<String> for Message {
+    fn from(raw: String) -> Self {
+        Message::parse(&raw)
+    }
+}
+
+impl Serialize for Message {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Message {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        Ok(Message::parse(&raw))
+    }
+}
@@ -0,0 +1,94 @@
+use std::str::FromStr;
+
+use reqwest::Method;
+use url::Url;
+
+use super::*;
+use crate::{message::Message, restful::send_message::MessageAuditData, secret::GroupSecret};
+
+/// `POST /v2/groups/{group_openid}/messages`
+///
+/// <https://bot.q.qq.com/wiki/develop/api/openapi/group/send_group_message.html>
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct SendGroupMessage {
+    content: Option<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    msg_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image: Option<String>,
+}
+
+impl SendGroupMessage {
+    pub fn new(content: impl Into<Message>) -> Self {
+        Self { content: Some(content.into()), ..Default::default() }
+    }
+    pub fn reply_to(mut self, msg_id: impl Into<String>) -> Self {
+        self.msg_id = Some(msg_id.into());
+        self
+    }
+    pub fn image(mut self, url: impl Into<String>) -> Self {
+        self.image = Some(url.into());
+        self
+    }
+    pub fn end_point(group_openid: &str) -> String {
+        if cfg!(debug_assertions) {
+            format!("https://sandbox.api.sgroup.qq.com/v2/groups/{group_openid}/messages")
+        }
+        else {
+            format!("https://api.sgroup.qq.com/v2/groups/{group_openid}/messages")
+        }
+    }
+    pub async fn send(self, key: &GroupSecret, group_openid: &str) -> AckermanResult<SendOpenMessageResponse> {
+        let url = Url::from_str(&Self::end_point(group_openid))?;
+        let response = key.as_request(Method::POST, url).await?.json(&self).send().await?;
+        Ok(response.json().await?)
+    }
+}
+
+/// `POST /v2/users/{user_openid}/messages`
+///
+/// <https://bot.q.qq.com/wiki/develop/api/openapi/c2c/send_c2c_message.html>
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct SendC2CMessage {
+    content: Option<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    msg_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image: Option<String>,
+}
+
+impl SendC2CMessage {
+    pub fn new(content: impl Into<Message>) -> Self {
+        Self { content: Some(content.into()), ..Default::default() }
+    }
+    pub fn reply_to(mut self, msg_id: impl Into<String>) -> Self {
+        self.msg_id = Some(msg_id.into());
+        self
+    }
+    pub fn image(mut self, url: impl Into<String>) -> Self {
+        self.image = Some(url.into());
+        self
+    }
+    pub fn end_point(user_openid: &str) -> String {
+        if cfg!(debug_assertions) {
+            format!("https://sandbox.api.sgroup.qq.com/v2/users/{user_openid}/messages")
+        }
+        else {
+            format!("https://api.sgroup.qq.com/v2/users/{user_openid}/messages")
+        }
+    }
+    pub async fn send(self, key: &GroupSecret, user_openid: &str) -> AckermanResult<SendOpenMessageResponse> {
+        let url = Url::from_str(&Self::end_point(user_openid))?;
+        let response = key.as_request(Method::POST, url).await?.json(&self).send().await?;
+        Ok(response.json().await?)
+    }
+}
+
+/// 群/单聊 v2 接口的发送结果，和频道场景下的 `SendMessageResponse` 共用审核载荷，
+/// 但 `timestamp` 在这两个接口里是数字而不是字符串，所以单独建模，不能直接复用那个类型。
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum SendOpenMessageResponse {
+    Sent { id: String, timestamp: i64 },
+    PendingAudit { data: MessageAuditData },
+}
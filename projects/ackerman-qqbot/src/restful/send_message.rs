@@ -3,15 +3,16 @@ use super::*;
 use reqwest::Method;
 
 use std::str::FromStr;
-use toml::Value;
 use url::Url;
 
+use crate::{event::MessageCreateEvent, message::Message};
+
 /// `GET /channels/{channel_id}/messages/{message_id}`
 ///
 /// <https://bot.q.qq.com/wiki/develop/api/openapi/message/get_message_of_id.html>
 #[derive(Debug)]
 pub struct GetMessageListResponse {
-    pub items: Vec<MessageItem>,
+    pub items: Vec<MessageCreateEvent>,
 }
 
 impl GetMessageListResponse {
@@ -37,36 +38,123 @@ impl GetMessageListResponse {
         if response.status().as_u16() > 300 {
             println!("{}", response.status().as_u16())
         }
+        let items: Vec<MessageCreateEvent> = response.json().await?;
+        Ok(Self { items })
+    }
+}
 
-        let value: Value = response.json().await?;
-        println!("{:#?}", value);
-        todo!();
+/// `POST /channels/{channel_id}/messages`
+///
+/// <https://bot.q.qq.com/wiki/develop/api/openapi/message/post_messages.html>
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct SendMessage {
+    content: Option<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    msg_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    embed: Option<MessageEmbed>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ark: Option<MessageArk>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    markdown: Option<MessageMarkdown>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image: Option<String>,
+}
+
+/// 结构化的卡片消息，与 `ark` 二选一使用
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct MessageEmbed {
+    pub title: String,
+    #[serde(default)]
+    pub prompt: String,
+    #[serde(default)]
+    pub fields: Vec<String>,
+}
+
+/// 由后台预先注册好的 ark 模板渲染出的卡片消息
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct MessageArk {
+    pub template_id: u32,
+    pub kv: Vec<MessageArkKv>,
+}
 
-        // Ok(Self { items: response.json().await? })
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MessageArkKv {
+    pub key: String,
+    pub value: String,
+}
+
+/// 由后台预先注册好的 markdown 模板渲染出的消息
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct MessageMarkdown {
+    pub template_id: u32,
+    pub params: Vec<MessageMarkdownParam>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MessageMarkdownParam {
+    pub key: String,
+    pub values: Vec<String>,
+}
+
+impl SendMessage {
+    pub fn new(content: impl Into<Message>) -> Self {
+        Self { content: Some(content.into()), ..Default::default() }
+    }
+    /// 被动回复某条消息（通常是收到 AT_MESSAGE_CREATE 之后原样带回 `msg_id`），而不是主动发言
+    pub fn reply_to(mut self, msg_id: impl Into<String>) -> Self {
+        self.msg_id = Some(msg_id.into());
+        self
+    }
+    pub fn embed(mut self, embed: MessageEmbed) -> Self {
+        self.embed = Some(embed);
+        self
     }
+    pub fn ark(mut self, ark: MessageArk) -> Self {
+        self.ark = Some(ark);
+        self
+    }
+    pub fn markdown(mut self, markdown: MessageMarkdown) -> Self {
+        self.markdown = Some(markdown);
+        self
+    }
+    pub fn image(mut self, url: impl Into<String>) -> Self {
+        self.image = Some(url.into());
+        self
+    }
+    pub fn end_point(key: &QQSecret) -> String {
+        if cfg!(debug_assertions) {
+            format!("https://sandbox.api.sgroup.qq.com/channels/{channel_id}/messages", channel_id = key.channel_id())
+        }
+        else {
+            format!("https://api.sgroup.qq.com/channels/{channel_id}/messages", channel_id = key.channel_id())
+        }
+    }
+    pub async fn send(self, key: &QQSecret) -> AckermanResult<SendMessageResponse> {
+        let url = Url::from_str(&Self::end_point(key))?;
+        let response = key.as_request(Method::POST, url).json(&self).send().await?;
+        let value: SendMessageResponse = response.json().await?;
+        Ok(value)
+    }
+}
+
+/// 正常发送成功会拿到新消息的 id，命中审核则先拿到一个 audit id，真正发出去时机器人会再收到一次 `MESSAGE_AUDIT_PASS` 事件
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum SendMessageResponse {
+    Sent { id: String, timestamp: String },
+    PendingAudit { data: MessageAuditData },
+}
+
+/// 命中审核时响应体里 `data` 字段的内容
+#[derive(Deserialize, Debug)]
+pub struct MessageAuditData {
+    pub message_audit: MessageAudit,
 }
 
 #[derive(Deserialize, Debug)]
-pub struct MessageItem {
-    /// 频道名称
-    pub name: String,
-    /// 描述
-    pub description: String,
-    /// 频道头像地址
-    #[serde(deserialize_with = "crate::utils::read_url")]
-    pub icon: Url,
-    /// 频道ID
-    #[serde(deserialize_with = "crate::utils::read_u64")]
-    pub id: u64,
-    /// 	最大成员数
-    pub max_members: u32,
-    /// 成员数
-    pub member_count: u32,
-    /// 当前人是否是创建人
-    pub owner: bool,
-    /// 创建人用户ID
-    #[serde(deserialize_with = "crate::utils::read_u64")]
-    pub owner_id: u64,
-    /// 加入时间
-    pub joined_at: String,
+pub struct MessageAudit {
+    pub audit_id: String,
+    #[serde(default)]
+    pub audit_time: String,
 }
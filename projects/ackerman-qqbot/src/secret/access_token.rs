@@ -0,0 +1,99 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use url::Url;
+
+use crate::AckermanResult;
+
+/// 距离真正过期时间提前续期的安全余量，避免请求发出去的瞬间 token 刚好过期
+const RENEW_BEFORE_EXPIRY: Duration = Duration::from_secs(60);
+
+/// 后台刷新任务在一次 `getAppAccessToken` 请求失败之后，重试之前等待的时间
+const RETRY_AFTER_FAILURE: Duration = Duration::from_secs(30);
+
+/// `AppID` + `AppSecret` 换来的 `access_token` 的本地缓存。
+///
+/// 群/单聊场景下每次请求都要带上有效的 `access_token`，[`Self::new`] 会立刻拉起一个后台任务，
+/// 在过期前 ~60s 自动用 `POST https://bots.qq.com/app/getAppAccessToken` 换新并一直续下去；
+/// 调用方只需要反复 `await` [`Self::token`]，正常情况下读到的都是缓存，不需要等一次网络往返。
+#[derive(Debug)]
+pub struct AppAccessTokenCache {
+    app_id: String,
+    client_secret: String,
+    cached: RwLock<Option<CachedToken>>,
+}
+
+#[derive(Clone, Debug)]
+struct CachedToken {
+    access_token: String,
+    expires_at: Instant,
+}
+
+#[derive(Serialize, Debug)]
+struct GetAppAccessTokenRequest<'a> {
+    #[serde(rename = "appId")]
+    app_id: &'a str,
+    #[serde(rename = "clientSecret")]
+    client_secret: &'a str,
+}
+
+#[derive(Deserialize, Debug)]
+struct GetAppAccessTokenResponse {
+    access_token: String,
+    #[serde(deserialize_with = "crate::utils::read_u64")]
+    expires_in: u64,
+}
+
+impl AppAccessTokenCache {
+    /// 创建缓存并立刻拉起后台续期任务；调用方应该把返回的 `Arc` 存起来复用，而不是反复 `new`
+    pub fn new(app_id: impl Into<String>, client_secret: impl Into<String>) -> Arc<Self> {
+        let cache =
+            Arc::new(Self { app_id: app_id.into(), client_secret: client_secret.into(), cached: RwLock::new(None) });
+        tokio::spawn(cache.clone().renew_forever());
+        cache
+    }
+    /// 拿到一个当前仍然有效的 `access_token`；正常情况下命中后台任务维护的缓存，
+    /// 只有缓存还没被填充过（刚启动、后台任务还没跑完第一轮）或者已经过期才会现场换一次
+    pub async fn token(&self) -> AckermanResult<String> {
+        if let Some(cached) = self.cached.read().await.as_ref() {
+            if cached.expires_at > Instant::now() {
+                return Ok(cached.access_token.clone());
+            }
+        }
+        self.refresh().await
+    }
+    /// 后台任务：换到新 token 之后睡到提前续期的时间点再换下一轮；换失败就退避一段时间重试
+    async fn renew_forever(self: Arc<Self>) {
+        loop {
+            let wait = match self.refresh().await {
+                Ok(_) => {
+                    let cached = self.cached.read().await;
+                    cached
+                        .as_ref()
+                        .map(|cached| cached.expires_at.saturating_duration_since(Instant::now()))
+                        .unwrap_or(RETRY_AFTER_FAILURE)
+                }
+                Err(error) => {
+                    println!("    刷新 access_token 失败, {} 秒后重试: {}", RETRY_AFTER_FAILURE.as_secs(), error);
+                    RETRY_AFTER_FAILURE
+                }
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+    async fn refresh(&self) -> AckermanResult<String> {
+        let url = Url::parse("https://bots.qq.com/app/getAppAccessToken")?;
+        let body = GetAppAccessTokenRequest { app_id: &self.app_id, client_secret: &self.client_secret };
+        let response: GetAppAccessTokenResponse =
+            reqwest::Client::new().request(Method::POST, url).json(&body).send().await?.json().await?;
+        let expires_at = Instant::now() + Duration::from_secs(response.expires_in).saturating_sub(RENEW_BEFORE_EXPIRY);
+        let access_token = response.access_token;
+        *self.cached.write().await = Some(CachedToken { access_token: access_token.clone(), expires_at });
+        Ok(access_token)
+    }
+}
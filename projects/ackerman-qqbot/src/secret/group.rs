@@ -0,0 +1,30 @@
+use std::sync::Arc;
+
+use reqwest::{header::AUTHORIZATION, Method, RequestBuilder};
+use url::Url;
+
+use crate::{secret::AppAccessTokenCache, AckermanResult};
+
+/// 群/单聊机器人的鉴权信息：不再用频道场景下的静态 `bot_token`，而是拿 `AppID` + `AppSecret`
+/// 换一个会过期的 `access_token`，每个请求都要带 `Authorization: QQBot <access_token>` 和
+/// `X-Union-Appid` 两个头。`as_request` 就是群模式下 `QQBotSecret::as_request` 的等价物。
+#[derive(Clone)]
+pub struct GroupSecret {
+    app_id: String,
+    tokens: Arc<AppAccessTokenCache>,
+}
+
+impl GroupSecret {
+    pub fn new(app_id: impl Into<String>, client_secret: impl Into<String>) -> Self {
+        let app_id = app_id.into();
+        let tokens = AppAccessTokenCache::new(app_id.clone(), client_secret);
+        Self { app_id, tokens }
+    }
+    pub async fn as_request(&self, method: Method, url: Url) -> AckermanResult<RequestBuilder> {
+        let token = self.tokens.token().await?;
+        Ok(reqwest::Client::new()
+            .request(method, url)
+            .header(AUTHORIZATION, format!("QQBot {token}"))
+            .header("X-Union-Appid", self.app_id.as_str()))
+    }
+}
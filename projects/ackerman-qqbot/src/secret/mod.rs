@@ -0,0 +1,5 @@
+pub mod access_token;
+pub mod group;
+
+pub use access_token::AppAccessTokenCache;
+pub use group::GroupSecret;
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde_json::{json, Value};
+
+use crate::event::EventBus;
+
+/// 签名请求的时间戳和服务器当前时间之间允许的最大偏差，超过这个范围一律拒绝，防止重放
+const MAX_SIGNATURE_AGE: i64 = 5 * 60;
+
+/// 除了一直占着一条出站 websocket 的网关模式，平台也支持把事件推到一个 HTTP 回调地址，
+/// 这个结构体就是那条路径的服务端：验签、解出 op-13 的验证挑战、把解码后的事件丢进和网关共用的
+/// [`EventBus`]，业务代码完全不用关心事件是从 webhook 还是 websocket 来的。
+pub struct QQBotWebhook {
+    signing_key: SigningKey,
+    verifying_key: VerifyingKey,
+    pub events: Arc<EventBus>,
+}
+
+impl QQBotWebhook {
+    /// `app_secret` 就是机器人的 `AppSecret`：把它重复/截断成 32 字节作为 Ed25519 的种子，
+    /// 和后台要求的推导方式保持一致，不需要额外生成或保存一份单独的密钥。
+    pub fn new(app_secret: &str) -> Self {
+        Self::with_bus(app_secret, Arc::new(EventBus::new()))
+    }
+    /// 和 [`Self::new`] 一样派生签名密钥，但复用调用方传入的事件总线，而不是新建一个；
+    /// 配合 [`crate::wss::QQBotWebsocket::link_with_bus`] 可以让网关和 webhook 两种传输方式
+    /// 共享同一批已注册的处理器，业务代码不用区分事件具体是从哪条通道来的。
+    pub fn with_bus(app_secret: &str, events: Arc<EventBus>) -> Self {
+        let signing_key = SigningKey::from_bytes(&Self::derive_seed(app_secret));
+        let verifying_key = signing_key.verifying_key();
+        Self { signing_key, verifying_key, events }
+    }
+    fn derive_seed(app_secret: &str) -> [u8; 32] {
+        let bytes = app_secret.as_bytes();
+        let mut seed = [0u8; 32];
+        if !bytes.is_empty() {
+            for (i, byte) in seed.iter_mut().enumerate() {
+                *byte = bytes[i % bytes.len()];
+            }
+        }
+        seed
+    }
+    /// 挂到 axum 的路由上，回调地址固定收 `POST /callback`
+    pub fn router(self: Arc<Self>) -> Router {
+        Router::new().route("/callback", post(Self::handle)).with_state(self)
+    }
+    async fn handle(State(webhook): State<Arc<Self>>, headers: HeaderMap, body: Bytes) -> (StatusCode, Json<Value>) {
+        let value: Value = match serde_json::from_slice(&body) {
+            Ok(value) => value,
+            Err(_) => return (StatusCode::BAD_REQUEST, Json(Value::Null)),
+        };
+        // op 13 是后台配置回调地址时下发的验证挑战，不带签名，直接用签名结果证明私钥匹配即可
+        if value.get("op").and_then(Value::as_u64) == Some(13) {
+            return webhook.validate_challenge(&value);
+        }
+        if !webhook.verify_signature(&headers, &body) {
+            println!("    webhook 签名校验失败");
+            return (StatusCode::UNAUTHORIZED, Json(Value::Null));
+        }
+        match serde_json::from_value::<crate::wss::QQBotOperation>(value) {
+            Ok(operation) => {
+                if let Some(event) = operation.event() {
+                    webhook.events.publish(event).await;
+                }
+            }
+            Err(error) => println!("    webhook 事件解码失败: {}", error),
+        }
+        (StatusCode::OK, Json(Value::Null))
+    }
+    fn validate_challenge(&self, value: &Value) -> (StatusCode, Json<Value>) {
+        let payload = value.get("d").cloned().unwrap_or_default();
+        let plain_token = payload.get("plain_token").and_then(Value::as_str).unwrap_or_default();
+        let event_ts = payload.get("event_ts").and_then(Value::as_str).unwrap_or_default();
+        let message = format!("{event_ts}{plain_token}");
+        let signature = self.signing_key.sign(message.as_bytes());
+        (StatusCode::OK, Json(json!({ "plain_token": plain_token, "signature": hex::encode(signature.to_bytes()) })))
+    }
+    /// 校验 `X-Signature-Ed25519`（对 `X-Signature-Timestamp` + 原始 body 签的名）是否匹配
+    fn verify_signature(&self, headers: &HeaderMap, body: &Bytes) -> bool {
+        let Some(signature) = headers.get("X-Signature-Ed25519").and_then(|value| value.to_str().ok()) else {
+            return false;
+        };
+        let Some(timestamp) = headers.get("X-Signature-Timestamp").and_then(|value| value.to_str().ok()) else {
+            return false;
+        };
+        let Ok(signed_at) = timestamp.parse::<i64>() else {
+            return false;
+        };
+        if (chrono::Utc::now().timestamp() - signed_at).abs() > MAX_SIGNATURE_AGE {
+            return false;
+        }
+        let Ok(signature_bytes) = hex::decode(signature) else {
+            return false;
+        };
+        let Ok(signature) = Signature::from_slice(&signature_bytes) else {
+            return false;
+        };
+        let mut message = Vec::with_capacity(timestamp.len() + body.len());
+        message.extend_from_slice(timestamp.as_bytes());
+        message.extend_from_slice(body);
+        self.verifying_key.verify(&message, &signature).is_ok()
+    }
+}
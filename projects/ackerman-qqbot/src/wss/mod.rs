@@ -3,18 +3,23 @@ use std::{
     fmt::{Debug, Formatter},
     net::{IpAddr, Ipv4Addr, SocketAddr},
     str::FromStr,
+    sync::Arc,
+    time::Duration,
 };
 
 use chrono::Utc;
 use futures_util::{SinkExt, StreamExt};
 use reqwest::Method;
 use serde::{Deserialize, Serialize};
-use serde_json::{from_str, to_string};
+use serde_json::{from_str, to_string, to_value, Value};
 use tokio::net::TcpStream;
 use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
 use url::Url;
 
-use crate::{AckermanResult, QQBotSecret};
+use crate::{
+    event::{EventBus, QQBotEvent},
+    AckermanResult, QQBotSecret,
+};
 
 pub struct QQBotWebsocket {
     pub wss: WebSocketStream<MaybeTlsStream<TcpStream>>,
@@ -22,6 +27,18 @@ pub struct QQBotWebsocket {
     connected: QQBotConnected,
     pub closed: bool,
     pub heartbeat_interval: u32,
+    /// 本次心跳是否已经收到 op 11 的确认，断线重连时用来判断服务端是否掉线
+    heartbeat_acked: bool,
+    /// 是否已经完成 Identify/Resume 并收到 READY/RESUMED，在此之前不发送心跳，
+    /// 避免 op 10 刚建立连接、ticker 第一次立即触发时就抢在鉴权前发出 op 1
+    identified: bool,
+    /// 最近一次 op 0 分发事件携带的序列号 `s`，Resume 时需要带上这个值
+    last_sequence: u32,
+    /// READY 事件下发的 `session_id`，为空时代表还没有可恢复的会话
+    session_id: String,
+    /// 解码后的事件分发给这里注册的处理器，详见 [`crate::event`]；用 `Arc` 包裹是为了能和
+    /// [`crate::webhook::QQBotWebhook`] 共用同一个总线，两种传输方式注册的处理器彼此可见
+    pub events: Arc<EventBus>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -57,20 +74,36 @@ pub struct User {
 }
 
 impl QQBotOperation {
-    pub fn dispatched(self) -> QQBotOperationDispatch {
-        match self.d {
-            QQBotOperationUnion::Dispatch(d) => d,
-            QQBotOperationUnion::Boolean(_) => Default::default(),
+    /// 把 `d` 解析成握手/鉴权用的 `QQBotOperationDispatch`（用于 op 0 的 READY、op 10 的 Hello 等）
+    pub fn dispatched(&self) -> QQBotOperationDispatch {
+        match &self.d {
+            QQBotOperationUnion::Dispatch(value) => serde_json::from_value(value.clone()).unwrap_or_default(),
+            _ => Default::default(),
+        }
+    }
+    /// 把 `d` 按照事件类型 `t` 解码成具体的 [`QQBotEvent`]，只有 `op == 0` 且 `t` 非空的真实事件才会有结果
+    pub fn event(&self) -> Option<QQBotEvent> {
+        match &self.d {
+            QQBotOperationUnion::Dispatch(value) if self.op == 0 && !self.t.is_empty() => {
+                Some(QQBotEvent::decode(&self.t, value.clone()))
+            }
+            _ => None,
         }
     }
+    /// op 9（Invalid Session）的载荷是一个布尔值，`true` 代表该会话仍然可以 Resume
+    pub fn resumable(&self) -> bool {
+        matches!(self.d, QQBotOperationUnion::Boolean(true))
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(untagged)]
 pub enum QQBotOperationUnion {
-    Dispatch(QQBotOperationDispatch),
     Boolean(bool),
     Integer(i32),
+    Resume(QQBotResume),
+    /// 捕获一切对象形态的载荷：握手鉴权信息、READY、以及各类消息/成员事件，具体结构由 `t` 决定
+    Dispatch(Value),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -90,6 +123,15 @@ pub struct QQBotOperationDispatch {
     #[serde(default)]
     pub user: User,
 }
+
+/// op 6（Resume）的载荷，携带鉴权 token、上次的 `session_id` 以及断线前收到的最后一个序列号
+#[derive(Serialize, Deserialize, Debug)]
+pub struct QQBotResume {
+    pub token: String,
+    pub session_id: String,
+    pub seq: u32,
+}
+
 impl Default for QQBotOperationUnion {
     fn default() -> Self {
         Self::Dispatch(Default::default())
@@ -127,10 +169,66 @@ impl Debug for QQBotWebsocket {
 
 impl QQBotWebsocket {
     pub async fn link(key: &QQBotSecret) -> AckermanResult<Self> {
+        Self::link_with_bus(key, Arc::new(EventBus::new())).await
+    }
+    /// 和 [`Self::link`] 一样建立网关连接，但复用调用方传入的事件总线，而不是新建一个；
+    /// 配合 [`crate::webhook::QQBotWebhook::with_bus`] 可以让网关和 webhook 两种传输方式
+    /// 共享同一批已注册的处理器
+    pub async fn link_with_bus(key: &QQBotSecret, events: Arc<EventBus>) -> AckermanResult<Self> {
         let url = Url::from_str("https://sandbox.api.sgroup.qq.com/gateway/bot")?;
         let value: QQBotConnected = key.as_request(Method::GET, url).send().await?.json().await?;
         let (wss, _) = connect_async(&value.url).await?;
-        Ok(Self { wss, key: key.clone(), connected: value, closed: false, heartbeat_interval: 40000 })
+        Ok(Self {
+            wss,
+            key: key.clone(),
+            connected: value,
+            closed: false,
+            heartbeat_interval: 40000,
+            heartbeat_acked: true,
+            identified: false,
+            last_sequence: 0,
+            session_id: "".to_string(),
+            events,
+        })
+    }
+    /// 自驱动的网关主循环：鉴权、定时发送心跳、断线自动重连/恢复会话，一直循环下去，只有出错时才返回 `Err`。
+    ///
+    /// 心跳由一个独立的 `tokio::time::interval` 驱动，和收包完全解耦：channel 再活跃也不会让 op 1 迟发,
+    /// 调用方 `await` 这个方法一次即可得到一条能够自愈的长连接；单次事件处理仍然可以单独调用 [`Self::next_event`]。
+    pub async fn run(&mut self) -> AckermanResult<()> {
+        let mut current_interval = self.heartbeat_interval;
+        let mut ticker = Self::heartbeat_ticker(current_interval);
+        loop {
+            if self.heartbeat_interval != current_interval {
+                current_interval = self.heartbeat_interval;
+                ticker = Self::heartbeat_ticker(current_interval);
+            }
+            tokio::select! {
+                _ = ticker.tick() => {
+                    if !self.identified {
+                        // 还没收到 READY/RESUMED，这一下是 ticker 创建时立即触发的第一次 tick，忽略
+                    } else if !self.heartbeat_acked {
+                        println!("    上一次心跳未被确认，判定连接已失效，尝试重连");
+                        self.reconnect().await?;
+                        ticker = Self::heartbeat_ticker(current_interval);
+                    } else {
+                        self.send_heartbeat().await?;
+                    }
+                }
+                result = self.next_event() => {
+                    result?;
+                    if self.closed {
+                        self.reconnect().await?;
+                        ticker = Self::heartbeat_ticker(current_interval);
+                    }
+                }
+            }
+        }
+    }
+    fn heartbeat_ticker(heartbeat_interval: u32) -> tokio::time::Interval {
+        let mut ticker = tokio::time::interval(Duration::from_millis(heartbeat_interval.max(1) as u64));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        ticker
     }
     pub async fn next_event(&mut self) -> AckermanResult {
         let op: QQBotOperation = match self.wss.next().await {
@@ -146,22 +244,63 @@ impl QQBotWebsocket {
                     _ => unreachable!("{:#?}", ss),
                 }
             }
-            None => return Ok(()),
+            None => {
+                self.closed = true;
+                println!("链接已关闭");
+                return Ok(());
+            }
         };
         println!("[{}] 协议 {}", Utc::now().format("%F %H:%M:%S"), op.op);
         match op.op {
             0 => {
-                println!("    鉴权成功, 登陆为 {:?}", op.dispatched().user.username);
+                if op.s != 0 {
+                    self.last_sequence = op.s;
+                }
+                if op.t == "READY" {
+                    let dispatch = op.dispatched();
+                    if !dispatch.session_id.is_empty() {
+                        self.session_id = dispatch.session_id.clone();
+                    }
+                    self.identified = true;
+                    println!("    鉴权成功, 登陆为 {:?}", dispatch.user.username);
+                }
+                else if op.t == "RESUMED" {
+                    self.identified = true;
+                    println!("    会话已恢复");
+                }
+                else if let Some(event) = op.event() {
+                    println!("    事件 {}", op.t);
+                    self.events.publish(event).await;
+                }
+            }
+            7 => {
+                println!("    服务端要求重连");
+                self.closed = true;
             }
             9 => {
-                println!("    鉴权参数有误");
+                if op.resumable() {
+                    println!("    会话已失效, 但可以 Resume");
+                }
+                else {
+                    println!("    鉴权参数有误, 重新 Identify");
+                    self.session_id = "".to_string();
+                }
+                self.closed = true;
             }
             10 => {
                 self.heartbeat_interval = op.dispatched().heartbeat_interval;
                 println!("    重设心跳间隔为 {}", self.heartbeat_interval);
+                if self.session_id.is_empty() {
+                    self.send_identify().await?;
+                }
+                else {
+                    self.send_resume().await?;
+                }
+            }
+            // 接收到心跳包确认
+            11 => {
+                self.heartbeat_acked = true;
             }
-            // 接收到心跳包
-            11 => {}
             _ => {
                 println!("未知协议 {:#?}", op);
             }
@@ -171,7 +310,13 @@ impl QQBotWebsocket {
     }
     pub async fn send_heartbeat(&mut self) -> AckermanResult<()> {
         println!("[{}] 协议 1", Utc::now().format("%F %H:%M:%S"));
-        let protocol = QQBotOperation { op: 1, s: 0, t: "".to_string(), d: QQBotOperationUnion::Integer(100) };
+        let protocol = QQBotOperation {
+            op: 1,
+            s: 0,
+            t: "".to_string(),
+            d: QQBotOperationUnion::Integer(self.last_sequence as i32),
+        };
+        self.heartbeat_acked = false;
         self.wss.send(Message::Text(to_string(&protocol)?)).await?;
         println!("    发送心跳包",);
         Ok(())
@@ -179,20 +324,45 @@ impl QQBotWebsocket {
     pub async fn send_identify(&mut self) -> AckermanResult<()> {
         println!("[{}] 协议 2", Utc::now().format("%F %H:%M:%S"));
         let intents = 1 << 9 | 1 << 10 | 1 << 26 | 1 << 30;
+        let identify = QQBotOperationDispatch {
+            token: self.key.bot_token(),
+            intents,
+            shard: vec![0, 1],
+            ..Default::default()
+        };
+        let protocol =
+            QQBotOperation { op: 2, s: 0, t: "".to_string(), d: QQBotOperationUnion::Dispatch(to_value(identify)?) };
+        println!("    监听掩码 {}", intents);
+        self.wss.send(Message::Text(to_string(&protocol)?)).await?;
+        println!("    首次连接鉴权");
+        Ok(())
+    }
+    /// 发送 op 6（Resume），携带上一次会话的 `session_id` 与最后收到的序列号，避免重新 Identify 丢失事件
+    pub async fn send_resume(&mut self) -> AckermanResult<()> {
+        println!("[{}] 协议 6", Utc::now().format("%F %H:%M:%S"));
         let protocol = QQBotOperation {
-            op: 2,
+            op: 6,
             s: 0,
             t: "".to_string(),
-            d: QQBotOperationUnion::Dispatch(QQBotOperationDispatch {
+            d: QQBotOperationUnion::Resume(QQBotResume {
                 token: self.key.bot_token(),
-                intents,
-                shard: vec![0, 1],
-                ..Default::default()
+                session_id: self.session_id.clone(),
+                seq: self.last_sequence,
             }),
         };
-        println!("    监听掩码 {}", intents);
         self.wss.send(Message::Text(to_string(&protocol)?)).await?;
-        println!("    首次连接鉴权");
+        println!("    恢复会话 session_id={}", self.session_id);
+        Ok(())
+    }
+    /// 重新建立到网关的 websocket 连接并重置心跳/鉴权状态；之后收到 op 10 时由 [`Self::next_event`]
+    /// 根据是否已有 `session_id` 决定 Resume 还是重新 Identify
+    async fn reconnect(&mut self) -> AckermanResult<()> {
+        println!("    正在重新连接网关 {}", self.connected.url);
+        let (wss, _) = connect_async(&self.connected.url).await?;
+        self.wss = wss;
+        self.closed = false;
+        self.heartbeat_acked = true;
+        self.identified = false;
         Ok(())
     }
 }
@@ -50,5 +50,17 @@ bitflags! {
         ///   - AT_MESSAGE_CREATE       // 当收到@机器人的消息时
         ///   - PUBLIC_MESSAGE_DELETE   // 当频道的消息被删除时
         const PUBLIC_GUILD_MESSAGES = 1 << 30;
+        /// 群和单聊事件，仅使用 `AppID` + `AppSecret` 换取 `access_token` 的群/单聊机器人能够设置此 intents。
+        ///   - GROUP_AT_MESSAGE_CREATE // 当群内 @ 机器人发送消息时
+        ///   - GROUP_ADD_ROBOT         // 当机器人被添加到群时
+        ///   - GROUP_DEL_ROBOT         // 当机器人被移除出群时
+        ///   - GROUP_MSG_REJECT        // 当群管理员关闭机器人的消息推送时
+        ///   - GROUP_MSG_RECEIVE       // 当群管理员打开机器人的消息推送时
+        ///   - C2C_MESSAGE_CREATE      // 当用户单聊发送消息时
+        ///   - FRIEND_ADD              // 当用户添加机器人为好友时
+        ///   - FRIEND_DEL              // 当用户删除机器人好友时
+        ///   - C2C_MSG_REJECT          // 当用户关闭单聊通知时
+        ///   - C2C_MSG_RECEIVE         // 当用户打开单聊通知时
+        const GROUP_AND_C2C_EVENT = 1 << 25;
     }
 }